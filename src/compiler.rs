@@ -0,0 +1,203 @@
+use crate::diagnostics::Diagnostics;
+use crate::lexer::{self, NativeSegment};
+use crate::parser::{Ast, CmpOp, Cond, Expr, OpKind, Stmt};
+use std::ops::Range;
+
+const OBJECTIVE: &str = "mcs";
+const NAMESPACE: &str = "mcs";
+const STORAGE: &str = "mcs:io";
+
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug)]
+pub struct Datapack {
+    pub functions: Vec<Function>,
+}
+
+enum Operand {
+    Holder(String),
+}
+
+// Carries the bits of state every statement kind needs: the counters behind
+// `__tmpN`/`native_N`/`if_N`/`while_N`/`match_N` names, and the generated
+// functions that `if`/`while` bodies, `match` arms and `native!()` calls are
+// split off into.
+struct Compiler {
+    tmp_count: usize,
+    native_count: usize,
+    block_count: usize,
+    functions: Vec<Function>,
+}
+
+impl Compiler {
+    fn fresh_tmp(&mut self) -> String {
+        let holder = format!("__tmp{}", self.tmp_count);
+        self.tmp_count += 1;
+        holder
+    }
+
+    fn fresh_block_name(&mut self, prefix: &str) -> String {
+        let name = format!("{prefix}_{}", self.block_count);
+        self.block_count += 1;
+        name
+    }
+}
+
+// Evaluates a postfix `expr_buf` range on a small operand stack, emitting a
+// `scoreboard players set` per `Num` node and two scoreboard commands per
+// `Op`/`Neg` node, and returns the holder that ends up carrying the result.
+fn compile_expr(c: &mut Compiler, lines: &mut Vec<String>, exprs: &[Expr], range: Range<usize>) -> Operand {
+    let mut stack: Vec<Operand> = Vec::new();
+
+    for expr in &exprs[range] {
+        match expr {
+            Expr::Num(n) => {
+                let holder = c.fresh_tmp();
+                lines.push(format!("scoreboard players set {holder} {OBJECTIVE} {n}"));
+                stack.push(Operand::Holder(holder));
+            },
+            Expr::Var(name) => stack.push(Operand::Holder(name.to_string())),
+            Expr::Neg => {
+                let Operand::Holder(src) = stack.pop().expect("unary minus with no operand");
+                let holder = c.fresh_tmp();
+                lines.push(format!("scoreboard players set {holder} {OBJECTIVE} 0"));
+                lines.push(format!("scoreboard players operation {holder} {OBJECTIVE} -= {src} {OBJECTIVE}"));
+                stack.push(Operand::Holder(holder));
+            },
+            Expr::Op(op) => {
+                let Operand::Holder(rhs) = stack.pop().expect("binary op with no rhs operand");
+                let Operand::Holder(lhs) = stack.pop().expect("binary op with no lhs operand");
+                let holder = c.fresh_tmp();
+                let sym = match op {
+                    OpKind::Add => "+=",
+                    OpKind::Sub => "-=",
+                    OpKind::Mul => "*=",
+                    OpKind::Div => "/=",
+                };
+                lines.push(format!("scoreboard players operation {holder} {OBJECTIVE} = {lhs} {OBJECTIVE}"));
+                lines.push(format!("scoreboard players operation {holder} {OBJECTIVE} {sym} {rhs} {OBJECTIVE}"));
+                stack.push(Operand::Holder(holder));
+            },
+        }
+    }
+
+    stack.pop().expect("expression produced no value")
+}
+
+// Evaluates both sides of a `Cond` into holders and maps it onto the
+// `execute if score <a> <obj> <op> <b> <obj>` comparison syntax.
+fn compile_cond(c: &mut Compiler, lines: &mut Vec<String>, exprs: &[Expr], cond: &Cond) -> (String, &'static str, String) {
+    let Operand::Holder(lhs) = compile_expr(c, lines, exprs, cond.lhs.clone());
+    let Operand::Holder(rhs) = compile_expr(c, lines, exprs, cond.rhs.clone());
+    let op = match cond.op {
+        CmpOp::Eq => "=",
+        CmpOp::Lt => "<",
+        CmpOp::Gt => ">",
+        CmpOp::Le => "<=",
+        CmpOp::Ge => ">=",
+    };
+    (lhs, op, rhs)
+}
+
+fn compile_stmts(c: &mut Compiler, lines: &mut Vec<String>, exprs: &[Expr], stmts: &[Stmt]) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::VarAssign { name, expr, .. } => {
+                let Operand::Holder(result) = compile_expr(c, lines, exprs, expr.clone());
+                lines.push(format!("scoreboard players operation {name} {OBJECTIVE} = {result} {OBJECTIVE}"));
+            },
+            Stmt::Native { raw, vars, .. } => {
+                for var in vars {
+                    lines.push(format!(
+                        "execute store result storage {STORAGE} {var} int 1 run scoreboard players get {var} {OBJECTIVE}"
+                    ));
+                }
+
+                // Already validated during parsing, so any segment errors here
+                // are unreachable; the string is rescanned only to rebuild it.
+                let mut diags = Diagnostics::new();
+                let mut command = String::from("$");
+                for segment in lexer::scan_native_segments(raw, 0, &mut diags) {
+                    match segment {
+                        NativeSegment::Text(text) => command.push_str(text),
+                        NativeSegment::Var(name) => command.push_str(&format!("$({name})")),
+                    }
+                }
+
+                let fn_name = format!("native_{}", c.native_count);
+                c.native_count += 1;
+                c.functions.push(Function { name: fn_name.clone(), lines: vec![command] });
+                lines.push(format!("function {NAMESPACE}:{fn_name} with storage {STORAGE}"));
+            },
+            Stmt::If { cond, body } => {
+                let (lhs, op, rhs) = compile_cond(c, lines, exprs, cond);
+
+                let fn_name = c.fresh_block_name("if");
+                let mut body_lines = Vec::new();
+                compile_stmts(c, &mut body_lines, exprs, body);
+                c.functions.push(Function { name: fn_name.clone(), lines: body_lines });
+
+                lines.push(format!(
+                    "execute if score {lhs} {OBJECTIVE} {op} {rhs} {OBJECTIVE} run function {NAMESPACE}:{fn_name}"
+                ));
+            },
+            Stmt::While { cond, body } => {
+                // The loop body is its own function that re-checks the
+                // condition and calls itself, so each iteration is a fresh
+                // invocation rather than growing the call stack.
+                let fn_name = c.fresh_block_name("while");
+
+                let mut loop_lines = Vec::new();
+                compile_stmts(c, &mut loop_lines, exprs, body);
+                let (lhs, op, rhs) = compile_cond(c, &mut loop_lines, exprs, cond);
+                loop_lines.push(format!(
+                    "execute if score {lhs} {OBJECTIVE} {op} {rhs} {OBJECTIVE} run function {NAMESPACE}:{fn_name}"
+                ));
+                c.functions.push(Function { name: fn_name.clone(), lines: loop_lines });
+
+                let (lhs, op, rhs) = compile_cond(c, lines, exprs, cond);
+                lines.push(format!(
+                    "execute if score {lhs} {OBJECTIVE} {op} {rhs} {OBJECTIVE} run function {NAMESPACE}:{fn_name}"
+                ));
+            },
+            Stmt::Match { scrutinee, arms, .. } => {
+                let Operand::Holder(holder) = compile_expr(c, lines, exprs, scrutinee.clone());
+                let matched_values: Vec<i32> = arms.iter().filter_map(|arm| arm.pattern).collect();
+
+                for arm in arms {
+                    let fn_name = c.fresh_block_name("match");
+                    let mut body_lines = Vec::new();
+                    compile_stmts(c, &mut body_lines, exprs, &arm.body);
+                    c.functions.push(Function { name: fn_name.clone(), lines: body_lines });
+
+                    match arm.pattern {
+                        Some(value) => lines.push(format!(
+                            "execute if score {holder} {OBJECTIVE} matches {value} run function {NAMESPACE}:{fn_name}"
+                        )),
+                        None => {
+                            let guards: String = matched_values
+                                .iter()
+                                .map(|value| format!("unless score {holder} {OBJECTIVE} matches {value} "))
+                                .collect();
+                            lines.push(format!("execute {guards}run function {NAMESPACE}:{fn_name}"));
+                        },
+                    }
+                }
+            },
+        }
+    }
+}
+
+pub fn compile(ast: Ast) -> Datapack {
+    let mut c = Compiler { tmp_count: 0, native_count: 0, block_count: 0, functions: Vec::new() };
+
+    let mut main = Vec::new();
+    compile_stmts(&mut c, &mut main, &ast.exprs, &ast.stmts);
+
+    c.functions.insert(0, Function { name: "main".to_string(), lines: main });
+    Datapack { functions: c.functions }
+}
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub span: Range<usize>,
+    pub message: String,
+}
+
+// Accumulates diagnostics for one compiler phase instead of aborting on the
+// first error, so a user sees every problem a phase can find in one run.
+#[derive(Debug, Default)]
+pub struct Diagnostics {
+    items: Vec<Diagnostic>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn error(&mut self, span: Range<usize>, message: impl Into<String>) {
+        self.items.push(Diagnostic { severity: Severity::Error, span, message: message.into() });
+    }
+
+    pub fn warning(&mut self, span: Range<usize>, message: impl Into<String>) {
+        self.items.push(Diagnostic { severity: Severity::Warning, span, message: message.into() });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.items.iter().any(|d| d.severity == Severity::Error)
+    }
+
+    pub fn error_count(&self) -> usize {
+        self.items.iter().filter(|d| d.severity == Severity::Error).count()
+    }
+
+    // Renders every diagnostic against `src`: the offending line, its
+    // line/column, and a `^^^` underline beneath the span. Sorted by span
+    // start so output reads top-to-bottom even when diagnostics were
+    // collected out of source order (e.g. across separate analysis passes).
+    pub fn render(&self, src: &str) {
+        let mut items: Vec<&Diagnostic> = self.items.iter().collect();
+        items.sort_by_key(|diag| diag.span.start);
+
+        for diag in items {
+            let (line_no, col_no, line_text) = locate(src, diag.span.start);
+            let label = match diag.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+            };
+            let gutter = line_no.to_string();
+
+            eprintln!("{label}: {}", diag.message);
+            eprintln!("{:width$} --> {line_no}:{col_no}", "", width = gutter.len());
+            eprintln!("{:width$} |", "", width = gutter.len());
+            eprintln!("{gutter} | {line_text}");
+            eprintln!(
+                "{:width$} | {}{}",
+                "",
+                " ".repeat(col_no.saturating_sub(1)),
+                "^".repeat(diag.span.len().max(1)),
+                width = gutter.len()
+            );
+        }
+    }
+}
+
+fn locate(src: &str, byte_pos: usize) -> (usize, usize, &str) {
+    let byte_pos = byte_pos.min(src.len());
+    let mut line_no = 1;
+    let mut line_start = 0;
+
+    for (i, b) in src.bytes().enumerate().take(byte_pos) {
+        if b == b'\n' {
+            line_no += 1;
+            line_start = i + 1;
+        }
+    }
+
+    let line_end = src[line_start..].find('\n').map(|i| line_start + i).unwrap_or(src.len());
+    let col_no = byte_pos - line_start + 1;
+    (line_no, col_no, &src[line_start..line_end])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locate_single_line_test() {
+        let src = "x = 1 + 2;";
+        assert_eq!(locate(src, 0), (1, 1, src));
+        assert_eq!(locate(src, 4), (1, 5, src));
+    }
+
+    #[test]
+    fn locate_multi_line_test() {
+        let src = "x = 1;\ny = 2;\nz = 3;";
+        assert_eq!(locate(src, 0), (1, 1, "x = 1;"));
+        assert_eq!(locate(src, 7), (2, 1, "y = 2;"));
+        assert_eq!(locate(src, 9), (2, 3, "y = 2;"));
+        assert_eq!(locate(src, 14), (3, 1, "z = 3;"));
+    }
+
+    #[test]
+    fn locate_clamps_to_src_len_test() {
+        let src = "x = 1;";
+        assert_eq!(locate(src, 1000), (1, src.len() + 1, src));
+    }
+
+    #[test]
+    fn error_and_warning_counted_separately_test() {
+        let mut diags = Diagnostics::new();
+        diags.error(0..1, "an error");
+        diags.warning(1..2, "a warning");
+        assert!(diags.has_errors());
+        assert_eq!(diags.error_count(), 1);
+        assert!(!diags.is_empty());
+    }
+
+    // `render` only prints, so sort-by-span can't be observed through its
+    // return value; assert on the sorted order directly instead, the same
+    // way `render` derives it.
+    #[test]
+    fn diagnostics_sorted_by_span_before_render_test() {
+        let mut diags = Diagnostics::new();
+        diags.error(10..11, "second");
+        diags.error(0..1, "first");
+        diags.warning(5..6, "middle");
+
+        let mut items: Vec<&Diagnostic> = diags.items.iter().collect();
+        items.sort_by_key(|diag| diag.span.start);
+
+        let messages: Vec<&str> = items.iter().map(|diag| diag.message.as_str()).collect();
+        assert_eq!(messages, vec!["first", "middle", "second"]);
+    }
+}
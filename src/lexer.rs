@@ -0,0 +1,299 @@
+use crate::diagnostics::Diagnostics;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    Name,
+    Num,
+    Str,
+    Eq,
+    EqEq,
+    FatArrow,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Bang,
+    Semicolon,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    LParen,
+    RParen,
+    LBrace,
+    RBrace,
+    Eof,
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub data: &'a str,
+    pub span: Range<usize>,
+}
+
+impl fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.kind)
+    }
+}
+
+pub struct Lexer<'a> {
+    src: &'a [u8],
+    pos: usize,
+    peeked: Option<Option<Token<'a>>>,
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(src: &'a [u8]) -> Self {
+        Self { src, pos: 0, peeked: None }
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = *self.src.get(self.pos)?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn skip_ws(&mut self) {
+        while matches!(self.src.get(self.pos), Some(b) if b.is_ascii_whitespace()) {
+            self.bump();
+        }
+    }
+
+    fn lex_next(&mut self, diags: &mut Diagnostics) -> Option<Token<'a>> {
+        self.skip_ws();
+
+        let start = self.pos;
+        let b = *self.src.get(self.pos)?;
+
+        if b == b'"' {
+            self.bump();
+            let content_start = self.pos;
+            loop {
+                match self.src.get(self.pos) {
+                    Some(b'"') => break,
+                    Some(_) => { self.bump(); },
+                    None => {
+                        diags.error(start..self.pos, "unterminated string literal");
+                        break;
+                    }
+                }
+            }
+            let content_end = self.pos;
+            self.bump(); // closing quote
+            let data = std::str::from_utf8(&self.src[content_start..content_end]).unwrap();
+            return Some(Token { kind: TokenKind::Str, data, span: start..self.pos });
+        }
+
+        let kind = match b {
+            b'+' => { self.bump(); TokenKind::Plus }
+            b'-' => { self.bump(); TokenKind::Minus }
+            b'*' => { self.bump(); TokenKind::Star }
+            b'/' => { self.bump(); TokenKind::Slash }
+            b'=' => {
+                self.bump();
+                match self.src.get(self.pos) {
+                    Some(b'=') => { self.bump(); TokenKind::EqEq }
+                    Some(b'>') => { self.bump(); TokenKind::FatArrow }
+                    _ => TokenKind::Eq,
+                }
+            }
+            b'<' => {
+                self.bump();
+                if self.src.get(self.pos) == Some(&b'=') {
+                    self.bump();
+                    TokenKind::Le
+                } else {
+                    TokenKind::Lt
+                }
+            }
+            b'>' => {
+                self.bump();
+                if self.src.get(self.pos) == Some(&b'=') {
+                    self.bump();
+                    TokenKind::Ge
+                } else {
+                    TokenKind::Gt
+                }
+            }
+            b'!' => { self.bump(); TokenKind::Bang }
+            b';' => { self.bump(); TokenKind::Semicolon }
+            b'(' => { self.bump(); TokenKind::LParen }
+            b')' => { self.bump(); TokenKind::RParen }
+            b'{' => { self.bump(); TokenKind::LBrace }
+            b'}' => { self.bump(); TokenKind::RBrace }
+            b'0'..=b'9' => {
+                while matches!(self.src.get(self.pos), Some(b'0'..=b'9')) {
+                    self.bump();
+                }
+                TokenKind::Num
+            }
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => {
+                while matches!(self.src.get(self.pos), Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_')) {
+                    self.bump();
+                }
+                TokenKind::Name
+            }
+            _ => {
+                self.bump();
+                diags.error(start..self.pos, format!("unexpected byte `{}`", b as char));
+                return self.lex_next(diags);
+            }
+        };
+
+        let data = std::str::from_utf8(&self.src[start..self.pos]).unwrap();
+        Some(Token { kind, data, span: start..self.pos })
+    }
+
+    pub fn next(&mut self, diags: &mut Diagnostics) -> Option<Token<'a>> {
+        match self.peeked.take() {
+            Some(tok) => tok,
+            None => self.lex_next(diags),
+        }
+    }
+
+    pub fn peek(&mut self, diags: &mut Diagnostics) -> Option<&Token<'a>> {
+        if self.peeked.is_none() {
+            self.peeked = Some(self.lex_next(diags));
+        }
+        self.peeked.as_ref().unwrap().as_ref()
+    }
+
+    fn eof_token(&self) -> Token<'a> {
+        Token { kind: TokenKind::Eof, data: "", span: self.pos..self.pos }
+    }
+
+    pub fn expect_next(&mut self, diags: &mut Diagnostics) -> Token<'a> {
+        match self.next(diags) {
+            Some(tok) => tok,
+            None => {
+                let tok = self.eof_token();
+                diags.error(tok.span.clone(), "unexpected end of input");
+                tok
+            }
+        }
+    }
+
+    pub fn expect_peek(&mut self, diags: &mut Diagnostics) -> &Token<'a> {
+        if self.peek(diags).is_none() {
+            let tok = self.eof_token();
+            diags.error(tok.span.clone(), "unexpected end of input");
+            self.peeked = Some(Some(tok));
+        }
+        self.peeked.as_ref().unwrap().as_ref().unwrap()
+    }
+
+    pub fn expect_specific_next(&mut self, diags: &mut Diagnostics, kind: TokenKind) -> Token<'a> {
+        let tok = self.expect_next(diags);
+        // `expect_next` already reported "unexpected end of input" for this
+        // exact token if it's `Eof`, so don't pile on a second diagnostic.
+        if tok.kind != kind && tok.kind != TokenKind::Eof {
+            diags.error(tok.span.clone(), format!("expected {kind}, got {}", tok.kind));
+        }
+        tok
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NativeSegment<'a> {
+    Text(&'a str),
+    Var(&'a str),
+}
+
+// Splits a `native!(...)` string literal into alternating text/`$ident` spans,
+// e.g. `"say $var0!"` => [Text("say "), Var("var0"), Text("!")]. `content_start`
+// is the absolute byte offset of `s`'s first byte in the source, so a malformed
+// `$` (not followed by an identifier) can be diagnosed at the right place.
+pub fn scan_native_segments<'a>(s: &'a str, content_start: usize, diags: &mut Diagnostics) -> Vec<NativeSegment<'a>> {
+    let bytes = s.as_bytes();
+    let mut segments = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] != b'$' {
+            i += 1;
+            continue;
+        }
+
+        let name_start = i + 1;
+        let mut name_end = name_start;
+        while matches!(bytes.get(name_end), Some(b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_')) {
+            name_end += 1;
+        }
+
+        if name_end == name_start {
+            diags.error(content_start + i..content_start + i + 1, "`$` must be followed by a variable name in native!()");
+            i += 1;
+            continue;
+        }
+
+        if text_start < i {
+            segments.push(NativeSegment::Text(&s[text_start..i]));
+        }
+        segments.push(NativeSegment::Var(&s[name_start..name_end]));
+
+        i = name_end;
+        text_start = name_end;
+    }
+
+    if text_start < s.len() {
+        segments.push(NativeSegment::Text(&s[text_start..]));
+    }
+
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_native_segments_test() {
+        use NativeSegment::*;
+
+        let map: &[(&str, &[NativeSegment])] = &[
+            ("say hi",               &[Text("say hi")]),
+            ("$var0",                &[Var("var0")]),
+            ("say $var0!",           &[Text("say "), Var("var0"), Text("!")]),
+            ("$a $b",                &[Var("a"), Text(" "), Var("b")]),
+            ("say $_foo9 to $bar",   &[Text("say "), Var("_foo9"), Text(" to "), Var("bar")]),
+        ];
+
+        for test in map {
+            let mut diags = Diagnostics::new();
+            let segments = scan_native_segments(test.0, 0, &mut diags);
+            assert!(diags.is_empty());
+            assert_eq!(segments.len(), test.1.len());
+            for (i, seg) in segments.iter().enumerate() {
+                assert_eq!(*seg, test.1[i]);
+            }
+        }
+    }
+
+    #[test]
+    fn scan_native_segments_extracts_vars() {
+        let mut diags = Diagnostics::new();
+        let segments = scan_native_segments("$a is $b, $a again", 0, &mut diags);
+        let vars: Vec<&str> =
+            segments.into_iter().filter_map(|seg| match seg { NativeSegment::Var(name) => Some(name), _ => None }).collect();
+        assert_eq!(vars, vec!["a", "b", "a"]);
+    }
+
+    #[test]
+    fn scan_native_segments_rejects_empty_name() {
+        let mut diags = Diagnostics::new();
+        let segments = scan_native_segments("say $ to nobody", 0, &mut diags);
+        assert!(diags.has_errors());
+        assert_eq!(segments, vec![NativeSegment::Text("say $ to nobody")]);
+    }
+}
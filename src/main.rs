@@ -2,63 +2,14 @@ mod lexer;
 mod parser;
 mod compiler;
 mod semantic;
+mod diagnostics;
 
+use diagnostics::Diagnostics;
 use std::io::prelude::*;
 use std::process::ExitCode;
 
 type Result<T> = std::result::Result<T, ()>;
 
-#[cfg(debug_assertions)]
-#[macro_export]
-macro_rules! exit_failure { () => { panic!(); } }
-
-#[cfg(not(debug_assertions))]
-#[macro_export]
-macro_rules! exit_failure { () => { std::process::exit(1); } }
-
-#[macro_export]
-macro_rules! lexical_err {
-    ($loc:expr, $($arg:tt)*) => {
-        eprint!("ERROR:{}: LexicalError: ", $loc);
-        eprintln!($($arg)*);
-        exit_failure!();
-    }
-}
-
-#[macro_export]
-macro_rules! syntax_err {
-    ($loc:expr, $($arg:tt)*) => {
-        eprint!("ERROR:{}: SyntaxError: ", $loc);
-        eprintln!($($arg)*);
-        exit_failure!();
-    }
-}
-
-#[macro_export]
-macro_rules! unexpected_token_err {
-    ($loc:expr, $t:ident) => {
-        syntax_err!($loc, "Unexpected {}", $t);
-    }
-}
-
-#[macro_export]
-macro_rules! semantic_err {
-    ($loc:expr, $($arg:tt)*) => {
-        eprint!("ERROR:{}: SemanticError: ", $loc);
-        eprintln!($($arg)*);
-        exit_failure!();
-    }
-}
-
-#[macro_export]
-macro_rules! compilation_err {
-    ($($arg:tt)*) => {
-        eprint!("ERROR: CompilationError: ");
-        eprintln!($($arg)*);
-        exit_failure!();
-    }
-}
-
 fn main2() -> Result<()> {
     let file_path = std::env::args().nth(1).ok_or_else(|| {
         eprintln!("ERROR: source file must be provided");
@@ -74,16 +25,36 @@ fn main2() -> Result<()> {
     })?;
 
     let mut lexer = lexer::Lexer::new(buffer.as_bytes()); // lexical analysis (lazy)
-    let ast = parser::parse(&mut lexer);                  // syntax  analysis
-    for fn_decl in &ast.fn_decls {
-        println!("{fn_decl:?}");
+
+    let mut diags = Diagnostics::new();
+    let mut ast = parser::parse(&mut lexer, &mut diags);  // syntax  analysis (lexing happens lazily inside)
+    if !diags.is_empty() {
+        diags.render(&buffer);
+    }
+    if diags.has_errors() {
+        return Err(());
     }
-    compiler::compile(ast);                               // compilation
 
+    for stmt in &ast.stmts {
+        println!("{stmt:?}");
+    }
 
-    //let program = parser::parse(&mut lexer);
-    //semantic::analyze(program);
-    //compiler::compile(program);
+    let mut diags = Diagnostics::new();
+    semantic::analyze(&mut ast, &mut diags);              // semantic analysis (incl. constant folding)
+    if !diags.is_empty() {
+        diags.render(&buffer);
+    }
+    if diags.has_errors() {
+        return Err(());
+    }
+
+    let datapack = compiler::compile(ast);                // compilation
+    for func in &datapack.functions {
+        println!("# function mcs:{}", func.name);
+        for line in &func.lines {
+            println!("{line}");
+        }
+    }
 
     Ok(())
 }
@@ -94,7 +65,3 @@ fn main() -> ExitCode {
         Ok(_)  => ExitCode::SUCCESS,
     }
 }
-
-
-
-// TODO: add something like that: `native!("say $var0")`
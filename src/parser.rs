@@ -1,10 +1,50 @@
+use crate::diagnostics::Diagnostics;
 use crate::lexer as lex;
 use std::process;
 use std::ops::Range;
 
 #[derive(Debug)]
 pub enum Stmt<'a> {
-    VarAssign { name: &'a str, expr: Range<usize> }
+    VarAssign { name: &'a str, span: Range<usize>, expr: Range<usize> },
+    Native { span: Range<usize>, raw: &'a str, vars: Vec<&'a str> },
+    If { cond: Cond, body: Vec<Stmt<'a>> },
+    While { cond: Cond, body: Vec<Stmt<'a>> },
+    Match { span: Range<usize>, scrutinee: Range<usize>, arms: Vec<MatchArm<'a>> },
+}
+
+// One `<num> => { ... }` or `_ => { ... }` arm of a `match`. `pattern` is
+// `None` for the default (`_`) arm. `span` anchors the pattern for
+// duplicate-pattern diagnostics.
+#[derive(Debug)]
+pub struct MatchArm<'a> {
+    pub span: Range<usize>,
+    pub pattern: Option<i32>,
+    pub body: Vec<Stmt<'a>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CmpOp {
+    Eq,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+}
+
+// A comparison between two postfix `expr_buf` ranges, e.g. `a < b + 1`.
+// `span` anchors diagnostics (e.g. constant-folding errors) at the operator.
+#[derive(Debug)]
+pub struct Cond {
+    pub span: Range<usize>,
+    pub op: CmpOp,
+    pub lhs: Range<usize>,
+    pub rhs: Range<usize>,
+}
+
+#[derive(Debug)]
+pub struct Ast<'a> {
+    pub exprs: Vec<Expr<'a>>,
+    pub stmts: Vec<Stmt<'a>>,
 }
 
 #[derive(Debug,  Clone, PartialEq)]
@@ -19,120 +59,334 @@ pub enum OpKind {
 pub enum Expr<'a> {
     Var(&'a str),
     Num(i32),
-    Op(OpKind)
+    Op(OpKind),
+    Neg,
 }
 
+fn precedence(op: &OpKind) -> u8 {
+    match op {
+        OpKind::Add | OpKind::Sub => 1,
+        OpKind::Mul | OpKind::Div => 2,
+    }
+}
 
+enum StackOp {
+    Paren,
+    Neg,
+    Op(OpKind),
+}
 
-pub fn parse<'a>(lex: &mut lex::Lexer<'a>) -> (Vec<Expr<'a>>, Vec<Stmt<'a>>) {
-    use lex::*;
+fn parse_num(src: &str) -> i32 {
+    src.parse::<i32>().unwrap_or_else(|err| {
+        eprintln!("ERROR: parser: could not parse num `{src}`: {err}");
+        process::exit(1);
+    })
+}
 
+
+
+pub fn parse<'a>(lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Ast<'a> {
     let mut expr_buf: Vec<Expr> = Vec::new();
     let mut stmt_buf: Vec<Stmt> = Vec::new();
 
-    loop {
-        if let Some(tok) = lex.next() {
-            match tok.kind {
-                TokenKind::Name => {
-                    let _ = lex.expect_specific_next(TokenKind::Eq);
-                    let expr = parse_expr(&mut expr_buf, lex);
-                    stmt_buf.push(Stmt::VarAssign { name: tok.data, expr });
-                    let _ = lex.expect_specific_next(TokenKind::Semicolon);
-                },
-                _ => todo!("now only variable assign stmt is avilable")
-            }
-        } else { return (expr_buf, stmt_buf); }
+    while lex.peek(diags).is_some() {
+        let errors_before = diags.error_count();
+
+        if let Some(stmt) = parse_stmt(&mut expr_buf, lex, diags) {
+            stmt_buf.push(stmt);
+        }
+
+        // Recover from a bad statement by skipping to the next `;` so later
+        // statements still get a chance to report their own diagnostics.
+        if diags.error_count() > errors_before {
+            recover(lex, diags);
+        }
     }
+
+    Ast { exprs: expr_buf, stmts: stmt_buf }
 }
 
-fn parse_expr<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>) -> Range<usize> {
-    use lex::*;
+// Keyword-dispatch: the leading token decides which statement form follows.
+fn parse_stmt<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Option<Stmt<'a>> {
+    use lex::TokenKind;
 
-    fn parse_num(src: &str) -> i32 {
-        src.parse::<i32>().unwrap_or_else(|err| {
-            eprintln!("ERROR: parser: could not parse num `{src}`: {err}");
-            process::exit(1);
-        })
+    let tok = lex.next(diags)?;
+
+    match tok.kind {
+        TokenKind::Name if tok.data == "native" && matches!(lex.peek(diags).map(|t| t.kind), Some(TokenKind::Bang)) => {
+            parse_native(lex, diags)
+        },
+        TokenKind::Name if tok.data == "if" => parse_if(expr_buf, lex, diags),
+        TokenKind::Name if tok.data == "while" => parse_while(expr_buf, lex, diags),
+        TokenKind::Name if tok.data == "match" => parse_match(tok, expr_buf, lex, diags),
+        TokenKind::Name => parse_var_assign(tok, expr_buf, lex, diags),
+        _ => {
+            diags.error(tok.span.clone(), format!("unexpected {}", tok.kind));
+            None
+        },
     }
+}
+
+fn parse_native<'a>(lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Option<Stmt<'a>> {
+    use lex::TokenKind;
 
-    // TODO: make seperate function for lexer. Something like `expect_oneof_next()`
-    fn expect_read<'a>(lex: &mut Lexer<'a>) -> Expr<'a> {
-        let tok = lex.expect_next();
-        match tok.kind {
-            TokenKind::Name => Expr::Var(tok.data),
-            TokenKind::Num  => Expr::Num(parse_num(tok.data)),
-            _ => unreachable!()
+    let _ = lex.expect_specific_next(diags, TokenKind::Bang);
+    let _ = lex.expect_specific_next(diags, TokenKind::LParen);
+    let str_tok = lex.expect_specific_next(diags, TokenKind::Str);
+    let _ = lex.expect_specific_next(diags, TokenKind::RParen);
+    let _ = lex.expect_specific_next(diags, TokenKind::Semicolon);
+
+    let vars = lex::scan_native_segments(str_tok.data, str_tok.span.start + 1, diags)
+        .into_iter()
+        .filter_map(|seg| match seg {
+            lex::NativeSegment::Var(name) => Some(name),
+            lex::NativeSegment::Text(_) => None,
+        })
+        .collect();
+
+    Some(Stmt::Native { span: str_tok.span, raw: str_tok.data, vars })
+}
+
+fn parse_var_assign<'a>(
+    tok: lex::Token<'a>,
+    expr_buf: &mut Vec<Expr<'a>>,
+    lex: &mut lex::Lexer<'a>,
+    diags: &mut Diagnostics,
+) -> Option<Stmt<'a>> {
+    use lex::TokenKind;
+
+    let span = tok.span.clone();
+    let _ = lex.expect_specific_next(diags, TokenKind::Eq);
+    let expr = parse_expr(expr_buf, lex, diags);
+    let _ = lex.expect_specific_next(diags, TokenKind::Semicolon);
+    Some(Stmt::VarAssign { name: tok.data, span, expr })
+}
+
+fn parse_cond<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Cond {
+    use lex::TokenKind;
+
+    let lhs = parse_expr(expr_buf, lex, diags);
+    let op_tok = lex.expect_next(diags);
+    let span = op_tok.span.clone();
+    let op = match op_tok.kind {
+        TokenKind::EqEq => CmpOp::Eq,
+        TokenKind::Lt => CmpOp::Lt,
+        TokenKind::Gt => CmpOp::Gt,
+        TokenKind::Le => CmpOp::Le,
+        TokenKind::Ge => CmpOp::Ge,
+        _ => {
+            diags.error(op_tok.span.clone(), format!("expected a comparison operator, got {}", op_tok.kind));
+            CmpOp::Eq
+        },
+    };
+    let rhs = parse_expr(expr_buf, lex, diags);
+
+    Cond { span, op, lhs, rhs }
+}
+
+fn parse_block<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Vec<Stmt<'a>> {
+    use lex::TokenKind;
+
+    let _ = lex.expect_specific_next(diags, TokenKind::LBrace);
+
+    let mut stmts = Vec::new();
+    while !matches!(lex.peek(diags).map(|t| t.kind), Some(TokenKind::RBrace) | None) {
+        let errors_before = diags.error_count();
+
+        if let Some(stmt) = parse_stmt(expr_buf, lex, diags) {
+            stmts.push(stmt);
+        }
+
+        if diags.error_count() > errors_before {
+            recover(lex, diags);
         }
     }
 
-    // 1 + 1     => 11+
-    // 1 + 1 + 1 => 11+ 1+ 1+ 1+ 1+ 1+
-    // 1 - 1 - 1 => 11- 1-
-    // 1 + 1 * 1 => 1 11* +
-    // 1 + 1 / 1 => 1 11/ +
+    let _ = lex.expect_specific_next(diags, TokenKind::RBrace);
+    stmts
+}
 
-    let mut ret = Range { start: expr_buf.len(), end: 0 };
+fn parse_if<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Option<Stmt<'a>> {
+    let cond = parse_cond(expr_buf, lex, diags);
+    let body = parse_block(expr_buf, lex, diags);
+    Some(Stmt::If { cond, body })
+}
 
-    expr_buf.push(expect_read(lex));
+fn parse_while<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Option<Stmt<'a>> {
+    let cond = parse_cond(expr_buf, lex, diags);
+    let body = parse_block(expr_buf, lex, diags);
+    Some(Stmt::While { cond, body })
+}
 
-    let mut prev_op = match lex.expect_peek().kind {
-        TokenKind::Plus  => OpKind::Add,
-        TokenKind::Minus => OpKind::Sub,
-        TokenKind::Star  => OpKind::Mul,
-        TokenKind::Slash => OpKind::Div,
-        _ => {
-            ret.end = expr_buf.len();
-            return ret;
+fn parse_match<'a>(
+    tok: lex::Token<'a>,
+    expr_buf: &mut Vec<Expr<'a>>,
+    lex: &mut lex::Lexer<'a>,
+    diags: &mut Diagnostics,
+) -> Option<Stmt<'a>> {
+    use lex::TokenKind;
+
+    let span = tok.span;
+    let scrutinee = parse_expr(expr_buf, lex, diags);
+    let _ = lex.expect_specific_next(diags, TokenKind::LBrace);
+
+    let mut arms = Vec::new();
+    while !matches!(lex.peek(diags).map(|t| t.kind), Some(TokenKind::RBrace) | None) {
+        let errors_before = diags.error_count();
+
+        let pat_tok = lex.expect_next(diags);
+        let pattern = if pat_tok.kind == TokenKind::Name && pat_tok.data == "_" {
+            None
+        } else if pat_tok.kind == TokenKind::Num {
+            Some(parse_num(pat_tok.data))
+        } else {
+            diags.error(pat_tok.span.clone(), format!("expected a match pattern (number or `_`), got {}", pat_tok.kind));
+            None
+        };
+
+        let _ = lex.expect_specific_next(diags, TokenKind::FatArrow);
+        let body = parse_block(expr_buf, lex, diags);
+
+        arms.push(MatchArm { span: pat_tok.span, pattern, body });
+
+        if diags.error_count() > errors_before {
+            recover(lex, diags);
         }
-    };
-    let _ = lex.next();
+    }
+
+    let _ = lex.expect_specific_next(diags, TokenKind::RBrace);
+    Some(Stmt::Match { span, scrutinee, arms })
+}
+
+fn recover<'a>(lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) {
+    use lex::TokenKind;
+
+    loop {
+        match lex.next(diags) {
+            Some(tok) if tok.kind == TokenKind::Semicolon => break,
+            Some(_) => continue,
+            None => break,
+        }
+    }
+}
+
+// Classic shunting-yard: `expr_buf` accumulates the postfix form directly,
+// `ops` is the operator stack (open parens, pending unary minuses, pending
+// binary ops). Unary minus is recognized whenever we're in "expect operand"
+// position (start of expression, right after another operator, or right
+// after `(`); it's given the highest precedence and right-associativity, so
+// it's always popped eagerly rather than compared against `prec`.
+fn parse_expr<'a>(expr_buf: &mut Vec<Expr<'a>>, lex: &mut lex::Lexer<'a>, diags: &mut Diagnostics) -> Range<usize> {
+    use lex::*;
+
+    let start = expr_buf.len();
+    let mut ops: Vec<StackOp> = Vec::new();
+    let mut expect_operand = true;
 
-    expr_buf.push(expect_read(lex));
     loop {
-        // 1 + 2 * 3 => 1 23* +
-        // 1 * 2 + 3 => 12* 3 +
-        // 1 * 2 * 3 => 1 23* *
-        // 1 + 2 * 3 * 4 => 1 23* 4* 5* 6*
-        // 1 + 2 * 3 + 2 => 1 23*+ 2
-        // 1 + 2 / 3 * 4 => 123/ 4*+
-        // 1 / 2 * 3 * 4 * 5 => 12/34*5*
-        // 1 / 2 / 3 * 4 / 5 => 1 23/ /4*5/
-
-        prev_op = match lex.expect_peek().kind {
-            TokenKind::Plus  => {
-                let _ = lex.next();
-                expr_buf.push(Expr::Op(prev_op));
-                OpKind::Add
+        let peeked = lex.expect_peek(diags);
+        let kind = peeked.kind;
+
+        // An operand is required at the start of the expression, right after
+        // an operator, right after `(`, and right after a unary minus. If
+        // none of the valid operand-starters show up here, bail out with a
+        // diagnostic instead of silently leaving a malformed postfix sequence
+        // for semantic analysis/codegen to choke on.
+        if expect_operand && !matches!(kind, TokenKind::Num | TokenKind::Name | TokenKind::LParen | TokenKind::Minus) {
+            // At true EOF, `expect_peek` already reported "unexpected end of
+            // input" for this position; don't pile on a second diagnostic.
+            if kind != TokenKind::Eof {
+                diags.error(peeked.span.clone(), format!("expected an operand, got {kind}"));
+            }
+            break;
+        }
+
+        match kind {
+            TokenKind::Num => {
+                let tok = lex.expect_next(diags);
+                expr_buf.push(Expr::Num(parse_num(tok.data)));
+                expect_operand = false;
+            },
+            TokenKind::Name => {
+                let tok = lex.expect_next(diags);
+                expr_buf.push(Expr::Var(tok.data));
+                expect_operand = false;
             },
-            TokenKind::Star  => {
-                let _ = lex.next();
-                if prev_op != OpKind::Div {
-                    expr_buf.push(expect_read(lex));
-                    expr_buf.push(Expr::Op(OpKind::Mul));
-                    continue;
+            TokenKind::LParen => {
+                let _ = lex.next(diags);
+                ops.push(StackOp::Paren);
+                expect_operand = true;
+            },
+            TokenKind::RParen => {
+                let span = lex.expect_peek(diags).span.clone();
+                let _ = lex.next(diags);
+                loop {
+                    match ops.pop() {
+                        Some(StackOp::Paren) => break,
+                        Some(StackOp::Op(op)) => expr_buf.push(Expr::Op(op)),
+                        Some(StackOp::Neg) => expr_buf.push(Expr::Neg),
+                        None => {
+                            diags.error(span, "unmatched `)`");
+                            break;
+                        },
+                    }
                 }
-                expr_buf.push(Expr::Op(prev_op));
-                OpKind::Mul
+                expect_operand = false;
+            },
+            TokenKind::Minus if expect_operand => {
+                let _ = lex.next(diags);
+                ops.push(StackOp::Neg);
+                expect_operand = true;
             },
-            TokenKind::Slash => {
-                let _ = lex.next();
-                if prev_op != OpKind::Mul {
-                    expr_buf.push(expect_read(lex));
-                    expr_buf.push(Expr::Op(OpKind::Div));
-                    continue;
+            TokenKind::Plus | TokenKind::Minus | TokenKind::Star | TokenKind::Slash => {
+                let op = match lex.expect_next(diags).kind {
+                    TokenKind::Plus  => OpKind::Add,
+                    TokenKind::Minus => OpKind::Sub,
+                    TokenKind::Star  => OpKind::Mul,
+                    TokenKind::Slash => OpKind::Div,
+                    _ => unreachable!(),
+                };
+                let prec = precedence(&op);
+                loop {
+                    let should_pop = match ops.last() {
+                        Some(StackOp::Paren) => false,
+                        Some(StackOp::Neg) => true,
+                        Some(StackOp::Op(top)) => precedence(top) >= prec,
+                        None => false,
+                    };
+                    if !should_pop { break; }
+                    match ops.pop().unwrap() {
+                        StackOp::Op(top) => expr_buf.push(Expr::Op(top)),
+                        StackOp::Neg => expr_buf.push(Expr::Neg),
+                        StackOp::Paren => unreachable!(),
+                    }
                 }
-                expr_buf.push(Expr::Op(prev_op));
-                OpKind::Div
+                ops.push(StackOp::Op(op));
+                expect_operand = true;
             },
-            _  => {
-                expr_buf.push(Expr::Op(prev_op));
-                ret.end = expr_buf.len();
-                return ret;
-            }
-        };
+            _ => break,
+        }
+    }
 
-        expr_buf.push(expect_read(lex));
+    while let Some(top) = ops.pop() {
+        match top {
+            StackOp::Op(op) => expr_buf.push(Expr::Op(op)),
+            StackOp::Neg => expr_buf.push(Expr::Neg),
+            StackOp::Paren => {
+                let tok = lex.expect_peek(diags);
+                // At true EOF, `expect_peek` already reported "unexpected end
+                // of input" for this position; don't report it again here.
+                // (`peek`'s `Option` goes `Some` again once `expect_peek` has
+                // cached the synthetic EOF token, so check `kind` explicitly.)
+                if tok.kind != TokenKind::Eof {
+                    let span = tok.span.clone();
+                    diags.error(span, "unmatched `(`");
+                }
+            },
+        }
     }
+
+    start..expr_buf.len()
 }
 
 
@@ -146,45 +400,160 @@ mod tests {
         use super::Expr::*;
 
         // Syntax: v1;v2;op
-        // 1 + 2          =>   12+
-        // 1 + 2 + 3      =>   12+ 3 +
-        // 1 + 2*3        =>   1 23* +
-        // 1 * 2 * 3      =>   1 23* *
-        // 1 + 2*3*4      =>   1 23* 4* +
-        // 1 + 2*3*4 + 5  =>   1 23* 4* + 5+
-        // 1 + 2*3 + 4*5  =>   1 23* + 45* +
-        // 1 / 2 * 3      =>   12/ 3*
-        // 1 / 2 * 3 * 4  =>   12/ 34**
-        // 1 / 2 * 3 / 4  =>   12/ 3* 4/
+        // 1 + 2               =>   1 2 +
+        // 1 + 2 + 3           =>   1 2 + 3 +
+        // 1 + 2*3             =>   1 2 3 * +
+        // 1 * 2 * 3           =>   1 2 * 3 *
+        // 1 + 2*3*4           =>   1 2 3 * 4 * +
+        // 1 + 2*3*4 + 5       =>   1 2 3 * 4 * + 5 +
+        // 1 + 2*3 + 4*5       =>   1 2 3 * + 4 5 * +
+        // 1 / 2 * 3           =>   1 2 / 3 *
+        // 1 / 2 * 3 * 4       =>   1 2 / 3 * 4 *
+        // 1 / 2 * 3 / 4       =>   1 2 / 3 * 4 /
+        // 1 / 2 / 3 * 4 / 5   =>   1 2 / 3 / 4 * 5 /
+        // (1 + 2) * 3         =>   1 2 + 3 *
+        // -1 + 2              =>   1 Neg 2 +
+        // -(1 + 2) * 3        =>   1 2 + Neg 3 *
         let map: &[(&str, &[Expr])] = &[
-            ("1 + 2;",         &[Num(1), Num(2), Op(OpKind::Add)]),
-            ("1 + 2 + 3;",     &[Num(1), Num(2), Op(OpKind::Add), Num(3), Op(OpKind::Add)]),
-            ("1 + 2*3;",       &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Op(OpKind::Add)]),
-            ("1 * 2 * 3;",     &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Op(OpKind::Mul)]),
-            ("1 + 2*3*4;",     &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Mul), Op(OpKind::Add)]),
-            ("1 + 2*3*4 + 5;", &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Mul), Op(OpKind::Add), Num(5), Op(OpKind::Add)]),
-            ("1 + 2*3 + 4*5;", &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Op(OpKind::Add), Num(4), Num(5), Op(OpKind::Mul), Op(OpKind::Add)]),
-            ("1 / 2 * 3;",     &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Mul)]),
-            ("1 / 2 * 3 * 4;", &[Num(1), Num(2), Op(OpKind::Div), Num(3), Num(4), Op(OpKind::Mul), Op(OpKind::Mul)]),
-            ("1 / 2 * 3 / 4;", &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Div)])
+            ("1 + 2;",             &[Num(1), Num(2), Op(OpKind::Add)]),
+            ("1 + 2 + 3;",         &[Num(1), Num(2), Op(OpKind::Add), Num(3), Op(OpKind::Add)]),
+            ("1 + 2*3;",           &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Op(OpKind::Add)]),
+            ("1 * 2 * 3;",         &[Num(1), Num(2), Op(OpKind::Mul), Num(3), Op(OpKind::Mul)]),
+            ("1 + 2*3*4;",         &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Mul), Op(OpKind::Add)]),
+            ("1 + 2*3*4 + 5;",     &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Mul), Op(OpKind::Add), Num(5), Op(OpKind::Add)]),
+            ("1 + 2*3 + 4*5;",     &[Num(1), Num(2), Num(3), Op(OpKind::Mul), Op(OpKind::Add), Num(4), Num(5), Op(OpKind::Mul), Op(OpKind::Add)]),
+            ("1 / 2 * 3;",         &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Mul)]),
+            ("1 / 2 * 3 * 4;",     &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Mul)]),
+            ("1 / 2 * 3 / 4;",     &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Mul), Num(4), Op(OpKind::Div)]),
+            ("1 / 2 / 3 * 4 / 5;", &[Num(1), Num(2), Op(OpKind::Div), Num(3), Op(OpKind::Div), Num(4), Op(OpKind::Mul), Num(5), Op(OpKind::Div)]),
+            ("(1 + 2) * 3;",       &[Num(1), Num(2), Op(OpKind::Add), Num(3), Op(OpKind::Mul)]),
+            ("-1 + 2;",            &[Num(1), Neg, Num(2), Op(OpKind::Add)]),
+            ("-(1 + 2) * 3;",      &[Num(1), Num(2), Op(OpKind::Add), Neg, Num(3), Op(OpKind::Mul)]),
         ];
 
         let mut exprs: Vec<Expr> = Vec::new();
         for test in map {
-            let range = parse_expr(&mut exprs, &mut lex::Lexer::new(test.0));
+            let mut diags = Diagnostics::new();
+            let range = parse_expr(&mut exprs, &mut lex::Lexer::new(test.0.as_bytes()), &mut diags);
+            assert!(diags.is_empty());
             for x in range {
                 assert_eq!(exprs[x], test.1[x]);
             }
 
             exprs.clear();
         }
+    }
 
-    //    let Stmt::VarAssign { name, expr } = &stmts[0];
-    //    assert_eq!(*name, "a");
-    //    assert_eq!(*expr, (0..expected.len()));
-    //
-    //    for (i, expr) in exprs.iter().enumerate() {
-    //        assert_eq!(*expr, expected[i]);
-    //    }
+    #[test]
+    fn missing_operand_test() {
+        // A missing operand must be diagnosed, not silently left as a
+        // malformed postfix sequence for semantic analysis/codegen to choke
+        // on (`x = ;` used to parse with zero diagnostics and then panic).
+        for src in ["x = ;", "x = 1 + ;"] {
+            let mut diags = Diagnostics::new();
+            let _ = parse(&mut lex::Lexer::new(src.as_bytes()), &mut diags);
+            assert!(diags.has_errors(), "expected a diagnostic for `{src}`");
+        }
+    }
+
+    #[test]
+    fn unmatched_paren_at_eof_test() {
+        // An unclosed `(` that runs straight into EOF must produce exactly
+        // one diagnostic, not one per expect_peek/expect_next call that
+        // happens to observe the same EOF.
+        let mut exprs: Vec<Expr> = Vec::new();
+        let mut diags = Diagnostics::new();
+        let _ = parse_expr(&mut exprs, &mut lex::Lexer::new(b"(1 + 2"), &mut diags);
+        assert_eq!(diags.error_count(), 1);
+    }
+
+    #[test]
+    fn if_while_test() {
+        use super::Expr::*;
+
+        let mut diags = Diagnostics::new();
+        let ast = parse(&mut lex::Lexer::new(b"if x < 5 { y = 1; }"), &mut diags);
+        assert!(diags.is_empty());
+        assert_eq!(ast.stmts.len(), 1);
+
+        match &ast.stmts[0] {
+            Stmt::If { cond, body } => {
+                assert_eq!(cond.op, CmpOp::Lt);
+                assert_eq!(ast.exprs[cond.lhs.clone()][0], Var("x"));
+                assert_eq!(ast.exprs[cond.rhs.clone()][0], Num(5));
+                assert_eq!(body.len(), 1);
+
+                match &body[0] {
+                    Stmt::VarAssign { name, expr, .. } => {
+                        assert_eq!(*name, "y");
+                        assert_eq!(ast.exprs[expr.clone()][0], Num(1));
+                    },
+                    _ => panic!("expected VarAssign body"),
+                }
+            },
+            _ => panic!("expected If"),
+        }
+
+        let mut diags = Diagnostics::new();
+        let ast = parse(&mut lex::Lexer::new(b"while a >= b { c = c - 1; }"), &mut diags);
+        assert!(diags.is_empty());
+        assert_eq!(ast.stmts.len(), 1);
+
+        match &ast.stmts[0] {
+            Stmt::While { cond, body } => {
+                assert_eq!(cond.op, CmpOp::Ge);
+                assert_eq!(ast.exprs[cond.lhs.clone()][0], Var("a"));
+                assert_eq!(ast.exprs[cond.rhs.clone()][0], Var("b"));
+                assert_eq!(body.len(), 1);
+
+                match &body[0] {
+                    Stmt::VarAssign { name, expr, .. } => {
+                        assert_eq!(*name, "c");
+                        let sub_expr = &ast.exprs[expr.clone()];
+                        assert_eq!(sub_expr.len(), 3);
+                        assert_eq!(sub_expr[0], Var("c"));
+                        assert_eq!(sub_expr[1], Num(1));
+                        assert_eq!(sub_expr[2], Op(OpKind::Sub));
+                    },
+                    _ => panic!("expected VarAssign body"),
+                }
+            },
+            _ => panic!("expected While"),
+        }
+    }
+
+    #[test]
+    fn match_test() {
+        use super::Expr::*;
+
+        let mut diags = Diagnostics::new();
+        let ast = parse(
+            &mut lex::Lexer::new(b"match x { 1 => { y = 1; } 2 => { y = 2; } _ => { y = 0; } }"),
+            &mut diags,
+        );
+        assert!(diags.is_empty());
+        assert_eq!(ast.stmts.len(), 1);
+
+        match &ast.stmts[0] {
+            Stmt::Match { scrutinee, arms, .. } => {
+                assert_eq!(ast.exprs[scrutinee.clone()][0], Var("x"));
+                assert_eq!(arms.len(), 3);
+                assert_eq!(arms[0].pattern, Some(1));
+                assert_eq!(arms[1].pattern, Some(2));
+                assert_eq!(arms[2].pattern, None);
+
+                for (arm, value) in [(&arms[0], 1), (&arms[1], 2), (&arms[2], 0)] {
+                    assert_eq!(arm.body.len(), 1);
+                    match &arm.body[0] {
+                        Stmt::VarAssign { name, expr, .. } => {
+                            assert_eq!(*name, "y");
+                            assert_eq!(ast.exprs[expr.clone()][0], Num(value));
+                        },
+                        _ => panic!("expected VarAssign arm body"),
+                    }
+                }
+            },
+            _ => panic!("expected Match"),
+        }
     }
 }
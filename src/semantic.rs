@@ -0,0 +1,243 @@
+use crate::diagnostics::Diagnostics;
+use crate::parser::{Ast, Expr, OpKind, Stmt};
+use std::collections::HashSet;
+use std::ops::Range;
+
+pub fn analyze(ast: &mut Ast, diags: &mut Diagnostics) {
+    let mut declared: HashSet<&str> = HashSet::new();
+    check_stmts(&ast.stmts, &mut declared, diags);
+
+    let mut shift: isize = 0;
+    fold_stmts(&mut ast.stmts, &mut ast.exprs, &mut shift, diags);
+}
+
+fn check_stmts<'a>(stmts: &[Stmt<'a>], declared: &mut HashSet<&'a str>, diags: &mut Diagnostics) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::VarAssign { name, .. } => {
+                declared.insert(name);
+            },
+            Stmt::Native { span, vars, .. } => {
+                for var in vars {
+                    if !declared.contains(var) {
+                        diags.error(span.clone(), format!("undeclared variable `{var}` referenced in native!()"));
+                    }
+                }
+            },
+            Stmt::If { body, .. } | Stmt::While { body, .. } => {
+                check_stmts(body, declared, diags);
+            },
+            Stmt::Match { span, arms, .. } => {
+                let mut seen: HashSet<i32> = HashSet::new();
+                let mut has_default = false;
+
+                for arm in arms {
+                    match arm.pattern {
+                        Some(value) => {
+                            if !seen.insert(value) {
+                                diags.error(arm.span.clone(), format!("duplicate pattern `{value}` in match"));
+                            }
+                        },
+                        None => has_default = true,
+                    }
+                    check_stmts(&arm.body, declared, diags);
+                }
+
+                if !has_default {
+                    diags.warning(span.clone(), "match has no default (`_`) arm");
+                }
+            },
+        }
+    }
+}
+
+// Folds constant subexpressions in place, e.g. `x = 2 * 3 + y` becomes
+// `x = 6 + y`. Statements share one growing `expr_buf`, and folding can
+// shrink a range, so `shift` accumulates how much every not-yet-visited
+// range needs to move by.
+fn fold_stmts<'a>(stmts: &mut [Stmt<'a>], exprs: &mut Vec<Expr<'a>>, shift: &mut isize, diags: &mut Diagnostics) {
+    for stmt in stmts {
+        match stmt {
+            Stmt::VarAssign { span, expr, .. } => {
+                fold_range(exprs, shift, expr, span, diags);
+            },
+            Stmt::Native { .. } => {},
+            Stmt::If { cond, body } | Stmt::While { cond, body } => {
+                let span = cond.span.clone();
+                fold_range(exprs, shift, &mut cond.lhs, &span, diags);
+                fold_range(exprs, shift, &mut cond.rhs, &span, diags);
+                fold_stmts(body, exprs, shift, diags);
+            },
+            Stmt::Match { span, scrutinee, arms } => {
+                let span = span.clone();
+                fold_range(exprs, shift, scrutinee, &span, diags);
+                for arm in arms {
+                    fold_stmts(&mut arm.body, exprs, shift, diags);
+                }
+            },
+        }
+    }
+}
+
+fn fold_range<'a>(
+    exprs: &mut Vec<Expr<'a>>,
+    shift: &mut isize,
+    range: &mut Range<usize>,
+    span: &Range<usize>,
+    diags: &mut Diagnostics,
+) {
+    range.start = (range.start as isize + *shift) as usize;
+    range.end = (range.end as isize + *shift) as usize;
+
+    let folded = fold_expr(&exprs[range.clone()], span, diags);
+    let delta = folded.len() as isize - range.len() as isize;
+
+    exprs.splice(range.clone(), folded);
+    range.end = (range.end as isize + delta) as usize;
+    *shift += delta;
+}
+
+enum Folded<'a> {
+    Num(i32),
+    Node(Vec<Expr<'a>>),
+}
+
+impl<'a> Folded<'a> {
+    fn into_nodes(self) -> Vec<Expr<'a>> {
+        match self {
+            Folded::Num(n) => vec![Expr::Num(n)],
+            Folded::Node(nodes) => nodes,
+        }
+    }
+}
+
+// Walks one postfix range on an evaluation stack: `Num`s fold eagerly, and an
+// `Op`/`Neg` whose operand(s) aren't all `Num` is reassembled symbolically
+// instead (its operand subtrees are already folded, just not collapsible
+// further).
+fn fold_expr<'a>(nodes: &[Expr<'a>], span: &Range<usize>, diags: &mut Diagnostics) -> Vec<Expr<'a>> {
+    let mut stack: Vec<Folded<'a>> = Vec::new();
+
+    for node in nodes {
+        match node {
+            Expr::Num(n) => stack.push(Folded::Num(*n)),
+            Expr::Var(name) => stack.push(Folded::Node(vec![Expr::Var(name)])),
+            Expr::Neg => {
+                let operand = stack.pop().expect("unary minus with no operand");
+                stack.push(match operand {
+                    Folded::Num(n) => Folded::Num(-n),
+                    Folded::Node(mut nodes) => {
+                        nodes.push(Expr::Neg);
+                        Folded::Node(nodes)
+                    },
+                });
+            },
+            Expr::Op(op) => {
+                let rhs = stack.pop().expect("binary op with no rhs operand");
+                let lhs = stack.pop().expect("binary op with no lhs operand");
+                stack.push(match (lhs, rhs) {
+                    (Folded::Num(a), Folded::Num(b)) => Folded::Num(match op {
+                        OpKind::Add => a.wrapping_add(b),
+                        OpKind::Sub => a.wrapping_sub(b),
+                        OpKind::Mul => a.wrapping_mul(b),
+                        OpKind::Div => {
+                            if b == 0 {
+                                diags.error(span.clone(), "division by zero in constant expression");
+                                0
+                            } else {
+                                a.wrapping_div(b)
+                            }
+                        },
+                    }),
+                    (lhs, rhs) => {
+                        let mut nodes = lhs.into_nodes();
+                        nodes.extend(rhs.into_nodes());
+                        nodes.push(Expr::Op(op.clone()));
+                        Folded::Node(nodes)
+                    },
+                });
+            },
+        }
+    }
+
+    stack.pop().expect("expression produced no value").into_nodes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer;
+
+    fn analyze_src(src: &str) -> (Ast<'_>, Diagnostics) {
+        let mut parse_diags = Diagnostics::new();
+        let mut ast = crate::parser::parse(&mut lexer::Lexer::new(src.as_bytes()), &mut parse_diags);
+        assert!(parse_diags.is_empty(), "parse errors for `{src}`: {parse_diags:?}");
+
+        let mut diags = Diagnostics::new();
+        analyze(&mut ast, &mut diags);
+        (ast, diags)
+    }
+
+    #[test]
+    fn fold_test() {
+        use Expr::*;
+
+        // Each case's exprs are the concatenation of every statement/cond/arm
+        // range's folded content, in source order, since ranges tile the
+        // shared expr_buf contiguously.
+        let cases: &[(&str, &[Expr])] = &[
+            ("x = 2 * 3 + y;", &[Num(6), Var("y"), Op(OpKind::Add)]),
+            (
+                "x = 1; if x < 2 * 3 { y = 4 / 2 + x; }",
+                &[Num(1), Var("x"), Num(6), Num(2), Var("x"), Op(OpKind::Add)],
+            ),
+            ("x = 1; while x < 10 / 2 { y = 3 * 3; }", &[Num(1), Var("x"), Num(5), Num(9)]),
+            (
+                "x = 5; match x { 1 => { y = 2 + 3; } _ => { y = 10 / 5; } }",
+                &[Num(5), Var("x"), Num(5), Num(2)],
+            ),
+            (
+                // Nested if-inside-while, to exercise the shift accounting
+                // across two levels of block nesting.
+                "while x < 2 { if y < 3 * 3 { z = 1 + 1; } }",
+                &[Var("x"), Num(2), Var("y"), Num(9), Num(2)],
+            ),
+        ];
+
+        for (src, expected) in cases {
+            let (ast, diags) = analyze_src(src);
+            assert!(diags.is_empty(), "unexpected diagnostics for `{src}`: {diags:?}");
+            assert_eq!(ast.exprs.len(), expected.len(), "folding mismatch for `{src}`");
+            for (got, want) in ast.exprs.iter().zip(expected.iter()) {
+                assert_eq!(got, want, "folding mismatch for `{src}`");
+            }
+        }
+    }
+
+    #[test]
+    fn fold_div_by_zero_test() {
+        let (ast, diags) = analyze_src("x = 5 / 0;");
+        assert!(diags.has_errors());
+        assert_eq!(ast.exprs.len(), 1);
+        assert_eq!(ast.exprs[0], Expr::Num(0));
+    }
+
+    #[test]
+    fn match_duplicate_pattern_test() {
+        let (_, diags) = analyze_src("match x { 1 => { y = 1; } 1 => { y = 2; } }");
+        assert!(diags.has_errors());
+    }
+
+    #[test]
+    fn match_missing_default_test() {
+        let (_, diags) = analyze_src("match x { 1 => { y = 1; } }");
+        assert!(!diags.has_errors());
+        assert!(!diags.is_empty());
+    }
+
+    #[test]
+    fn match_no_warning_with_default_test() {
+        let (_, diags) = analyze_src("match x { 1 => { y = 1; } _ => { y = 0; } }");
+        assert!(diags.is_empty());
+    }
+}